@@ -3,6 +3,7 @@ use actix_web::body::MessageBody;
 use actix_web::dev::{ServiceRequest, ServiceResponse};
 use actix_web::http::StatusCode;
 use actix_web::{Error, ResponseError};
+use std::marker::PhantomData;
 use tracing::Span;
 
 /// `RootSpanBuilder` allows you to customise the root span attached by
@@ -14,6 +15,41 @@ pub trait RootSpanBuilder {
     fn on_request_end<B: MessageBody>(span: Span, outcome: &Result<ServiceResponse<B>, Error>);
 }
 
+/// The HTTP coordinates of a request - scheme, host and target - derived from
+/// [`ServiceRequest::connection_info`] and [`ServiceRequest::uri`].
+///
+/// [`root_span!`](crate::root_span!) captures `method`, `endpoint` and `client_ip` out of the box,
+/// but custom [`RootSpanBuilder`]s that want the full HTTP coordinate set - e.g. to follow the
+/// OpenTelemetry semantic conventions, like [`OtelRootSpanBuilder`] does - would otherwise have to
+/// re-derive it themselves. `HttpRouteInfo` is that derivation, factored out so it doesn't have to
+/// be reimplemented by every downstream builder.
+pub struct HttpRouteInfo {
+    pub scheme: String,
+    pub host: String,
+    /// Path and, if present, query string, per the OpenTelemetry `http.target` convention
+    /// (e.g. `/foo?bar=1`).
+    pub target: String,
+}
+
+impl HttpRouteInfo {
+    pub fn from_request(request: &ServiceRequest) -> Self {
+        let connection_info = request.connection_info();
+        let scheme = connection_info.scheme().to_owned();
+        let host = connection_info.host().to_owned();
+        let target = request
+            .uri()
+            .path_and_query()
+            .map(|path_and_query| path_and_query.as_str())
+            .unwrap_or_else(|| request.uri().path())
+            .to_owned();
+        Self {
+            scheme,
+            host,
+            target,
+        }
+    }
+}
+
 /// The default [`RootSpanBuilder`] for [`TracingLogger`].
 ///
 /// It captures:
@@ -52,6 +88,97 @@ impl RootSpanBuilder for DefaultRootSpanBuilder {
     }
 }
 
+/// Decides which [`Level`](crate::Level) the root span for an incoming request should be
+/// created at.
+///
+/// Implement this on a zero-sized marker type and plug it into
+/// [`DynamicLevelRootSpanBuilder`] to route noisy, high-volume endpoints - health checks, metrics
+/// scrapes, and the like - to `DEBUG`/`TRACE`, while keeping everything else at `INFO`, instead of
+/// being stuck with the fixed `Level::INFO` that [`DefaultRootSpanBuilder`] bakes in.
+pub trait SpanLevel {
+    fn level(request: &ServiceRequest) -> crate::Level;
+}
+
+/// A [`RootSpanBuilder`] whose span level is picked per-request by `L`, rather than the fixed
+/// `Level::INFO` [`DefaultRootSpanBuilder`] uses.
+///
+/// A span's level is fixed for its whole lifetime, so `on_request_end` can't retroactively lower
+/// or raise it - but a 5xx outcome is never allowed to go unnoticed just because `L` picked a low
+/// level for the route: when the response is a server error, an `ERROR`-level event is emitted
+/// from inside the span, in addition to the usual `status`/`exception.*` fields that
+/// [`DefaultRootSpanBuilder`] records.
+pub struct DynamicLevelRootSpanBuilder<L>(PhantomData<L>);
+
+impl<L: SpanLevel> RootSpanBuilder for DynamicLevelRootSpanBuilder<L> {
+    fn on_request_start(request: &ServiceRequest) -> Span {
+        let level = L::level(request);
+        root_span!(level = level, request)
+    }
+
+    fn on_request_end<B: MessageBody>(span: Span, outcome: &Result<ServiceResponse<B>, Error>) {
+        let status_code = match &outcome {
+            // use the status code already constructed for the outgoing HTTP response - it may
+            // diverge from `ResponseError::status_code()` (a custom `error_response()` impl, or a
+            // later middleware rewriting the status), and we want to agree with the `status`
+            // field that `DefaultRootSpanBuilder::on_request_end` is about to record below.
+            Ok(response) => response.response().status(),
+            Err(error) => error.as_response_error().status_code(),
+        };
+        if status_code.is_server_error() {
+            let _entered = span.enter();
+            tracing::error!("request failed with a server error");
+        }
+
+        DefaultRootSpanBuilder::on_request_end(span, outcome);
+    }
+}
+
+#[cfg(test)]
+mod dynamic_level_tests {
+    use super::span_capture::CapturingSubscriber;
+    use super::{DynamicLevelRootSpanBuilder, RootSpanBuilder, SpanLevel};
+    use actix_web::dev::ServiceRequest;
+    use actix_web::test::TestRequest;
+    use actix_web::HttpResponse;
+    use tracing::Level;
+
+    struct AlwaysDebug;
+
+    impl SpanLevel for AlwaysDebug {
+        fn level(_request: &ServiceRequest) -> crate::Level {
+            crate::Level::DEBUG
+        }
+    }
+
+    type Builder = DynamicLevelRootSpanBuilder<AlwaysDebug>;
+
+    #[test]
+    fn server_error_emits_an_error_event_even_at_a_low_span_level() {
+        let (subscriber, captured) = CapturingSubscriber::new();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let request = TestRequest::default().to_srv_request();
+        let span = Builder::on_request_start(&request);
+        let response = request.into_response(HttpResponse::InternalServerError().finish());
+        Builder::on_request_end(span, &Ok(response));
+
+        assert_eq!(captured.event_count_at(Level::ERROR), 1);
+    }
+
+    #[test]
+    fn client_error_does_not_emit_an_error_event() {
+        let (subscriber, captured) = CapturingSubscriber::new();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let request = TestRequest::default().to_srv_request();
+        let span = Builder::on_request_start(&request);
+        let response = request.into_response(HttpResponse::BadRequest().finish());
+        Builder::on_request_end(span, &Ok(response));
+
+        assert_eq!(captured.event_count_at(Level::ERROR), 0);
+    }
+}
+
 fn handle_error(span: Span, status_code: StatusCode, response_error: &dyn ResponseError) {
     // pre-formatting errors is a workaround for https://github.com/tokio-rs/tracing/issues/1565
     let display = format!("{response_error}");
@@ -62,3 +189,568 @@ fn handle_error(span: Span, status_code: StatusCode, response_error: &dyn Respon
 
     span.record("status", code);
 }
+
+/// A [`RootSpanBuilder`] that follows the [OpenTelemetry semantic conventions for HTTP
+/// servers](https://github.com/open-telemetry/opentelemetry-specification/blob/main/specification/trace/semantic_conventions/http.md)
+/// instead of [`DefaultRootSpanBuilder`]'s ad-hoc field names.
+///
+/// It captures:
+/// - `otel.kind`, always set to `"server"`;
+/// - HTTP method (`http.method`);
+/// - HTTP scheme (`http.scheme`);
+/// - HTTP host (`http.host`);
+/// - HTTP target, i.e. the request path and query string, if any (`http.target`);
+/// - HTTP status code (`http.status_code`);
+/// - `Display` (`exception.message`) and `Debug` (`exception.details`) representations of the error, if there was an error;
+/// - `otel.status_code`, set to `"OK"` for 4xx responses (the server did its job - the client sent a bad
+///   request) and `"ERROR"` for 5xx/transport errors, so that OTLP collectors classify the span correctly.
+///
+/// [`TracingLogger`]: crate::TracingLogger
+pub struct OtelRootSpanBuilder;
+
+impl RootSpanBuilder for OtelRootSpanBuilder {
+    fn on_request_start(request: &ServiceRequest) -> Span {
+        let http_method = request.method().as_str();
+        let HttpRouteInfo {
+            scheme: http_scheme,
+            host: http_host,
+            target: http_target,
+        } = HttpRouteInfo::from_request(request);
+
+        tracing::info_span!(
+            "HTTP request",
+            otel.kind = "server",
+            http.method = %http_method,
+            http.scheme = %http_scheme,
+            http.host = %http_host,
+            http.target = %http_target,
+            http.status_code = tracing::field::Empty,
+            otel.status_code = tracing::field::Empty,
+            exception.message = tracing::field::Empty,
+            exception.details = tracing::field::Empty,
+        )
+    }
+
+    fn on_request_end<B: MessageBody>(span: Span, outcome: &Result<ServiceResponse<B>, Error>) {
+        match &outcome {
+            Ok(response) => {
+                if let Some(error) = response.response().error() {
+                    // use the status code already constructed for the outgoing HTTP response
+                    otel_handle_error(span, response.status(), error.as_response_error());
+                } else {
+                    otel_record_status(&span, response.status());
+                }
+            }
+            Err(error) => {
+                let response_error = error.as_response_error();
+                otel_handle_error(span, response_error.status_code(), response_error);
+            }
+        };
+    }
+}
+
+fn otel_record_status(span: &Span, status_code: StatusCode) {
+    let code: i32 = status_code.as_u16().into();
+    span.record("http.status_code", code);
+
+    // a plain 5xx `HttpResponse` built without going through `ResponseError` (e.g.
+    // `HttpResponse::InternalServerError().finish()`) lands here rather than in
+    // `otel_handle_error`, so it needs the same server-error classification.
+    let otel_status_code = if status_code.is_server_error() {
+        "ERROR"
+    } else {
+        "OK"
+    };
+    span.record("otel.status_code", otel_status_code);
+}
+
+fn otel_handle_error(span: Span, status_code: StatusCode, response_error: &dyn ResponseError) {
+    // pre-formatting errors is a workaround for https://github.com/tokio-rs/tracing/issues/1565
+    let display = format!("{response_error}");
+    let debug = format!("{response_error:?}");
+    span.record("exception.message", &tracing::field::display(display));
+    span.record("exception.details", &tracing::field::display(debug));
+    let code: i32 = status_code.as_u16().into();
+    span.record("http.status_code", code);
+
+    // a 4xx is a client error: the server handled the request correctly, so the span is not
+    // an exception from an OTel point of view. Only 5xx/transport errors are.
+    let otel_status_code = if status_code.is_client_error() {
+        "OK"
+    } else {
+        "ERROR"
+    };
+    span.record("otel.status_code", otel_status_code);
+}
+
+#[cfg(test)]
+mod otel_status_tests {
+    use super::span_capture::CapturingSubscriber;
+    use super::{OtelRootSpanBuilder, RootSpanBuilder};
+    use actix_web::body::BoxBody;
+    use actix_web::test::TestRequest;
+    use actix_web::HttpResponse;
+
+    #[test]
+    fn plain_5xx_response_without_an_attached_error_is_recorded_as_error() {
+        let (subscriber, captured) = CapturingSubscriber::new();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let request = TestRequest::default().to_srv_request();
+        let span = OtelRootSpanBuilder::on_request_start(&request);
+        let response = request.into_response(HttpResponse::InternalServerError().finish());
+        OtelRootSpanBuilder::on_request_end(span, &Ok(response));
+
+        assert_eq!(captured.field("otel.status_code").as_deref(), Some("ERROR"));
+        assert_eq!(captured.field("http.status_code").as_deref(), Some("500"));
+    }
+
+    #[test]
+    fn plain_4xx_response_without_an_attached_error_is_recorded_as_ok() {
+        let (subscriber, captured) = CapturingSubscriber::new();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let request = TestRequest::default().to_srv_request();
+        let span = OtelRootSpanBuilder::on_request_start(&request);
+        let response = request.into_response(HttpResponse::BadRequest().finish());
+        OtelRootSpanBuilder::on_request_end(span, &Ok(response));
+
+        assert_eq!(captured.field("otel.status_code").as_deref(), Some("OK"));
+        assert_eq!(captured.field("http.status_code").as_deref(), Some("400"));
+    }
+
+    #[test]
+    fn response_error_with_a_4xx_status_is_recorded_as_ok() {
+        use actix_web::error::ErrorNotFound;
+
+        let (subscriber, captured) = CapturingSubscriber::new();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let request = TestRequest::default().to_srv_request();
+        let span = OtelRootSpanBuilder::on_request_start(&request);
+        let outcome = Err(ErrorNotFound("not found"));
+        OtelRootSpanBuilder::on_request_end::<BoxBody>(span, &outcome);
+
+        assert_eq!(captured.field("otel.status_code").as_deref(), Some("OK"));
+        assert_eq!(captured.field("http.status_code").as_deref(), Some("404"));
+    }
+
+    #[test]
+    fn response_error_with_a_5xx_status_is_recorded_as_error() {
+        use actix_web::error::ErrorInternalServerError;
+
+        let (subscriber, captured) = CapturingSubscriber::new();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let request = TestRequest::default().to_srv_request();
+        let span = OtelRootSpanBuilder::on_request_start(&request);
+        let outcome = Err(ErrorInternalServerError("boom"));
+        OtelRootSpanBuilder::on_request_end::<BoxBody>(span, &outcome);
+
+        assert_eq!(captured.field("otel.status_code").as_deref(), Some("ERROR"));
+        assert_eq!(captured.field("http.status_code").as_deref(), Some("500"));
+    }
+}
+
+/// A [`RootSpanBuilder`] that extends [`OtelRootSpanBuilder`] with
+/// [W3C Trace Context](https://www.w3.org/TR/trace-context/) propagation.
+///
+/// On top of everything [`OtelRootSpanBuilder`] captures, it parses the incoming `traceparent`
+/// header (if any) and records the 32 hex-character trace-id it carries into a `trace_id` span
+/// field. The field is left empty - [`tracing::field::Empty`] - when the header is missing or
+/// malformed, e.g. a version/trace-id/parent-id that doesn't parse as hex, or an all-zero
+/// trace-id/parent-id (both of which the W3C spec calls out as invalid).
+///
+/// [`TracingLogger`]: crate::TracingLogger
+pub struct TraceContextRootSpanBuilder;
+
+impl RootSpanBuilder for TraceContextRootSpanBuilder {
+    fn on_request_start(request: &ServiceRequest) -> Span {
+        let http_method = request.method().as_str();
+        let HttpRouteInfo {
+            scheme: http_scheme,
+            host: http_host,
+            target: http_target,
+        } = HttpRouteInfo::from_request(request);
+
+        let trace_id = request
+            .headers()
+            .get("traceparent")
+            .and_then(|header| header.to_str().ok())
+            .and_then(parse_traceparent);
+
+        let span = tracing::info_span!(
+            "HTTP request",
+            otel.kind = "server",
+            http.method = %http_method,
+            http.scheme = %http_scheme,
+            http.host = %http_host,
+            http.target = %http_target,
+            http.status_code = tracing::field::Empty,
+            otel.status_code = tracing::field::Empty,
+            exception.message = tracing::field::Empty,
+            exception.details = tracing::field::Empty,
+            trace_id = tracing::field::Empty,
+        );
+        if let Some(trace_id) = trace_id {
+            span.record("trace_id", &trace_id.as_str());
+        }
+        span
+    }
+
+    fn on_request_end<B: MessageBody>(span: Span, outcome: &Result<ServiceResponse<B>, Error>) {
+        OtelRootSpanBuilder::on_request_end(span, outcome)
+    }
+}
+
+/// Parses a `traceparent` header value per the W3C Trace Context spec -
+/// `version "-" trace-id "-" parent-id "-" flags` - and returns the trace-id as a 32
+/// hex-character string, or `None` if the header is malformed or the trace-id/parent-id is
+/// all-zero (which the spec treats as meaning "no trace context").
+fn parse_traceparent(value: &str) -> Option<String> {
+    let mut parts = value.split('-');
+    let version = parts.next()?;
+    let trace_id = parts.next()?;
+    let parent_id = parts.next()?;
+    let flags = parts.next()?;
+    if parts.next().is_some() {
+        return None;
+    }
+
+    if version.len() != 2 || trace_id.len() != 32 || parent_id.len() != 16 || flags.len() != 2 {
+        return None;
+    }
+    if !version.bytes().all(|b| b.is_ascii_hexdigit())
+        || !trace_id.bytes().all(|b| b.is_ascii_hexdigit())
+        || !parent_id.bytes().all(|b| b.is_ascii_hexdigit())
+        || !flags.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        return None;
+    }
+    if trace_id.bytes().all(|b| b == b'0') || parent_id.bytes().all(|b| b == b'0') {
+        return None;
+    }
+
+    Some(trace_id.to_owned())
+}
+
+#[cfg(test)]
+mod parse_traceparent_tests {
+    use super::parse_traceparent;
+
+    #[test]
+    fn valid_header_returns_the_trace_id() {
+        let header = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        assert_eq!(
+            parse_traceparent(header).as_deref(),
+            Some("4bf92f3577b34da6a3ce929d0e0e4736")
+        );
+    }
+
+    #[test]
+    fn empty_header_value_is_none() {
+        assert_eq!(parse_traceparent(""), None);
+    }
+
+    #[test]
+    fn wrong_segment_count_is_none() {
+        assert_eq!(
+            parse_traceparent("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7"),
+            None
+        );
+        assert_eq!(
+            parse_traceparent("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra"),
+            None
+        );
+    }
+
+    #[test]
+    fn wrong_segment_lengths_are_none() {
+        assert_eq!(
+            parse_traceparent("0-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+            None
+        );
+        assert_eq!(
+            parse_traceparent("00-4bf92f3577b34da6a3ce929d0e0e473-00f067aa0ba902b7-01"),
+            None
+        );
+        assert_eq!(
+            parse_traceparent("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b-01"),
+            None
+        );
+        assert_eq!(
+            parse_traceparent("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-1"),
+            None
+        );
+    }
+
+    #[test]
+    fn non_hex_characters_are_none() {
+        assert_eq!(
+            parse_traceparent("zz-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+            None
+        );
+        assert_eq!(
+            parse_traceparent("00-4bf92f3577b34da6a3ce929d0e0e473g-00f067aa0ba902b7-01"),
+            None
+        );
+    }
+
+    #[test]
+    fn all_zero_trace_id_is_none() {
+        assert_eq!(
+            parse_traceparent("00-00000000000000000000000000000000-00f067aa0ba902b7-01"),
+            None
+        );
+    }
+
+    #[test]
+    fn all_zero_parent_id_is_none() {
+        assert_eq!(
+            parse_traceparent("00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01"),
+            None
+        );
+    }
+}
+
+/// How an error encountered while handling a request should be recorded on the root span.
+///
+/// Returned by [`ErrorClassifier::classify`] to let expected, high-volume 4xx conditions -
+/// validation failures, 404s, and the like - skip the heavyweight `Debug`-formatting that
+/// [`DefaultRootSpanBuilder`] always pays for, without losing visibility into genuine server
+/// errors.
+pub enum ErrorClass {
+    /// Don't record anything about the error on the span - not even `status`.
+    Ignore,
+    /// Record `status`, but skip the `exception.message`/`exception.details` formatting and
+    /// don't mark the span as an exception.
+    RecordAsClientError,
+    /// Record `status`, `exception.message` and `exception.details`, same as
+    /// [`DefaultRootSpanBuilder`] does for every error today.
+    RecordAsServerError,
+}
+
+/// Classifies an error encountered while handling a request, to decide how - or whether - it
+/// should show up on the request's root span.
+///
+/// Implement this on a zero-sized marker type and plug it into
+/// [`ClassifyingRootSpanBuilder`] so that expected client errors don't flood your logs with
+/// `exception.details` dumps.
+pub trait ErrorClassifier {
+    fn classify(status_code: StatusCode, response_error: &dyn ResponseError) -> ErrorClass;
+}
+
+/// A [`RootSpanBuilder`] that defers to `C: `[`ErrorClassifier`] to decide how each error should
+/// be recorded, instead of unconditionally recording the full `exception.message`/
+/// `exception.details` breakdown the way [`DefaultRootSpanBuilder`] does.
+pub struct ClassifyingRootSpanBuilder<C>(PhantomData<C>);
+
+impl<C: ErrorClassifier> RootSpanBuilder for ClassifyingRootSpanBuilder<C> {
+    fn on_request_start(request: &ServiceRequest) -> Span {
+        DefaultRootSpanBuilder::on_request_start(request)
+    }
+
+    fn on_request_end<B: MessageBody>(span: Span, outcome: &Result<ServiceResponse<B>, Error>) {
+        match &outcome {
+            Ok(response) => {
+                if let Some(error) = response.response().error() {
+                    let response_error = error.as_response_error();
+                    let status_code = response.status();
+                    let class = C::classify(status_code, response_error);
+                    record_classified(&span, status_code, response_error, class);
+                } else {
+                    let code: i32 = response.response().status().as_u16().into();
+                    span.record("status", code);
+                }
+            }
+            Err(error) => {
+                let response_error = error.as_response_error();
+                let status_code = response_error.status_code();
+                let class = C::classify(status_code, response_error);
+                record_classified(&span, status_code, response_error, class);
+            }
+        };
+    }
+}
+
+fn record_classified(
+    span: &Span,
+    status_code: StatusCode,
+    response_error: &dyn ResponseError,
+    class: ErrorClass,
+) {
+    match class {
+        ErrorClass::Ignore => {}
+        ErrorClass::RecordAsClientError => {
+            let code: i32 = status_code.as_u16().into();
+            span.record("status", code);
+        }
+        ErrorClass::RecordAsServerError => handle_error(span.clone(), status_code, response_error),
+    }
+}
+
+#[cfg(test)]
+mod classifying_tests {
+    use super::span_capture::CapturingSubscriber;
+    use super::{ClassifyingRootSpanBuilder, ErrorClass, ErrorClassifier, RootSpanBuilder};
+    use actix_web::body::BoxBody;
+    use actix_web::error::{ErrorBadRequest, ErrorInternalServerError, ErrorNotFound};
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+    use actix_web::ResponseError;
+
+    struct TestClassifier;
+
+    impl ErrorClassifier for TestClassifier {
+        fn classify(status_code: StatusCode, _response_error: &dyn ResponseError) -> ErrorClass {
+            match status_code {
+                StatusCode::NOT_FOUND => ErrorClass::Ignore,
+                StatusCode::BAD_REQUEST => ErrorClass::RecordAsClientError,
+                _ => ErrorClass::RecordAsServerError,
+            }
+        }
+    }
+
+    type Builder = ClassifyingRootSpanBuilder<TestClassifier>;
+
+    #[test]
+    fn ignored_errors_record_nothing() {
+        let (subscriber, captured) = CapturingSubscriber::new();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let request = TestRequest::default().to_srv_request();
+        let span = Builder::on_request_start(&request);
+        Builder::on_request_end::<BoxBody>(span, &Err(ErrorNotFound("not found")));
+
+        assert_eq!(captured.field("status"), None);
+        assert_eq!(captured.field("exception.message"), None);
+    }
+
+    #[test]
+    fn client_errors_record_only_the_status() {
+        let (subscriber, captured) = CapturingSubscriber::new();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let request = TestRequest::default().to_srv_request();
+        let span = Builder::on_request_start(&request);
+        Builder::on_request_end::<BoxBody>(span, &Err(ErrorBadRequest("bad request")));
+
+        assert_eq!(captured.field("status").as_deref(), Some("400"));
+        assert_eq!(captured.field("exception.message"), None);
+    }
+
+    #[test]
+    fn server_errors_record_the_full_exception_breakdown() {
+        let (subscriber, captured) = CapturingSubscriber::new();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let request = TestRequest::default().to_srv_request();
+        let span = Builder::on_request_start(&request);
+        Builder::on_request_end::<BoxBody>(span, &Err(ErrorInternalServerError("boom")));
+
+        assert_eq!(captured.field("status").as_deref(), Some("500"));
+        assert!(captured.field("exception.message").is_some());
+        assert!(captured.field("exception.details").is_some());
+    }
+}
+
+/// Minimal [`tracing::Subscriber`] that records the fields and events passed to it, so that
+/// `#[cfg(test)]` modules in this file can assert on what a [`RootSpanBuilder`] recorded without
+/// pulling in a full tracing-subscriber dependency.
+#[cfg(test)]
+mod span_capture {
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Metadata, Subscriber};
+
+    #[derive(Default)]
+    struct State {
+        fields: HashMap<String, String>,
+        events: Vec<(Level, String)>,
+    }
+
+    #[derive(Clone, Default)]
+    pub(super) struct Captured(Arc<Mutex<State>>);
+
+    impl Captured {
+        pub(super) fn field(&self, name: &str) -> Option<String> {
+            self.0.lock().unwrap().fields.get(name).cloned()
+        }
+
+        pub(super) fn event_count_at(&self, level: Level) -> usize {
+            self.0
+                .lock()
+                .unwrap()
+                .events
+                .iter()
+                .filter(|(l, _)| *l == level)
+                .count()
+        }
+    }
+
+    struct FieldVisitor<'a>(&'a mut HashMap<String, String>);
+
+    impl Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(field.name().to_owned(), format!("{value:?}"));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_owned(), value.to_owned());
+        }
+
+        fn record_i64(&mut self, field: &Field, value: i64) {
+            self.0.insert(field.name().to_owned(), value.to_string());
+        }
+    }
+
+    pub(super) struct CapturingSubscriber(Captured);
+
+    impl CapturingSubscriber {
+        pub(super) fn new() -> (Self, Captured) {
+            let captured = Captured::default();
+            (Self(captured.clone()), captured)
+        }
+    }
+
+    impl Subscriber for CapturingSubscriber {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let mut state = self.0 .0.lock().unwrap();
+            attrs.record(&mut FieldVisitor(&mut state.fields));
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &Id, values: &Record<'_>) {
+            let mut state = self.0 .0.lock().unwrap();
+            values.record(&mut FieldVisitor(&mut state.fields));
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut state = self.0 .0.lock().unwrap();
+            let mut message = String::new();
+            struct MessageVisitor<'a>(&'a mut String);
+            impl Visit for MessageVisitor<'_> {
+                fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                    if field.name() == "message" {
+                        *self.0 = format!("{value:?}");
+                    }
+                }
+            }
+            event.record(&mut MessageVisitor(&mut message));
+            state.events.push((*event.metadata().level(), message));
+        }
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+}